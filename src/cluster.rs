@@ -0,0 +1,99 @@
+//! Cluster selection. `RPC_URL`, `PRIORITY_FEE_URL`, and `SWAP_BASE` used to
+//! be compile-time constants pinned to mainnet; this module resolves them
+//! from `CLUSTER` (or explicit env overrides) so the whole flow can be
+//! pointed at devnet/testnet without recompiling.
+
+use std::env;
+
+use crate::swap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cluster {
+    #[default]
+    Mainnet,
+    Devnet,
+    Testnet,
+}
+
+impl Cluster {
+    pub fn from_env_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" | "mainnet-beta" => Some(Cluster::Mainnet),
+            "devnet" => Some(Cluster::Devnet),
+            "testnet" => Some(Cluster::Testnet),
+            _ => None,
+        }
+    }
+
+    pub fn is_mainnet(self) -> bool {
+        matches!(self, Cluster::Mainnet)
+    }
+
+    fn default_rpc_url(self) -> &'static str {
+        match self {
+            Cluster::Mainnet => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+        }
+    }
+}
+
+/// The resolved endpoints for a run, whether they came from `CLUSTER`'s
+/// defaults or explicit overrides.
+pub struct Endpoints {
+    pub cluster: Cluster,
+    pub rpc_url: String,
+    pub priority_fee_url: String,
+    pub swap_base: String,
+}
+
+impl Endpoints {
+    pub fn from_env() -> Self {
+        let cluster = env::var("CLUSTER")
+            .ok()
+            .and_then(|s| Cluster::from_env_str(&s))
+            .unwrap_or_default();
+
+        let rpc_url =
+            env::var("RPC_URL").unwrap_or_else(|_| cluster.default_rpc_url().to_string());
+        let priority_fee_url =
+            env::var("PRIORITY_FEE_URL").unwrap_or_else(|_| swap::PRIORITY_FEE_URL.to_string());
+        let swap_base = env::var("SWAP_BASE").unwrap_or_else(|_| swap::SWAP_BASE.to_string());
+
+        println!(
+            "cluster: {:?} rpc_url: {} swap_base: {}",
+            cluster, rpc_url, swap_base
+        );
+
+        Endpoints {
+            cluster,
+            rpc_url,
+            priority_fee_url,
+            swap_base,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_env_str_accepts_known_aliases() {
+        assert_eq!(Cluster::from_env_str("mainnet"), Some(Cluster::Mainnet));
+        assert_eq!(
+            Cluster::from_env_str("mainnet-beta"),
+            Some(Cluster::Mainnet)
+        );
+        assert_eq!(Cluster::from_env_str("DEVNET"), Some(Cluster::Devnet));
+        assert_eq!(Cluster::from_env_str("Testnet"), Some(Cluster::Testnet));
+        assert_eq!(Cluster::from_env_str("bogus"), None);
+    }
+
+    #[test]
+    fn is_mainnet_is_true_only_for_mainnet() {
+        assert!(Cluster::Mainnet.is_mainnet());
+        assert!(!Cluster::Devnet.is_mainnet());
+        assert!(!Cluster::Testnet.is_mainnet());
+    }
+}
@@ -0,0 +1,110 @@
+//! Priority-fee estimation. The Raydium `auto-fee` endpoint returns three
+//! tiers (`vh`/`h`/`m`); callers pick one via a [`ConfirmationTarget`]
+//! instead of reaching into [`crate::swap::FeeTiers`] directly, so the send
+//! loop can escalate tiers when a broadcast stalls.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+
+use crate::swap::{self, FeeTiers};
+
+/// How urgently the caller wants the transaction to land. Maps to the
+/// `vh`/`h`/`m` tiers of the `auto-fee` response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfirmationTarget {
+    Fast,
+    #[default]
+    Normal,
+    Economy,
+}
+
+impl ConfirmationTarget {
+    pub fn from_env_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "fast" => Some(ConfirmationTarget::Fast),
+            "normal" => Some(ConfirmationTarget::Normal),
+            "economy" => Some(ConfirmationTarget::Economy),
+            _ => None,
+        }
+    }
+
+    /// The next-higher tier to escalate to when a broadcast stalls, if any.
+    pub fn escalate(self) -> Option<Self> {
+        match self {
+            ConfirmationTarget::Economy => Some(ConfirmationTarget::Normal),
+            ConfirmationTarget::Normal => Some(ConfirmationTarget::Fast),
+            ConfirmationTarget::Fast => None,
+        }
+    }
+}
+
+fn tier_for(target: ConfirmationTarget, tiers: &FeeTiers) -> u64 {
+    match target {
+        ConfirmationTarget::Fast => tiers.vh,
+        ConfirmationTarget::Normal => tiers.h,
+        ConfirmationTarget::Economy => tiers.m,
+    }
+}
+
+#[async_trait]
+pub trait FeeEstimator {
+    async fn get_priority_fee(&self, target: ConfirmationTarget) -> Result<u64>;
+}
+
+/// Default estimator, backed by Raydium's `auto-fee` endpoint.
+pub struct AutoFeeEstimator<'a> {
+    pub http_client: &'a Client,
+    pub priority_fee_url: &'a str,
+}
+
+#[async_trait]
+impl<'a> FeeEstimator for AutoFeeEstimator<'a> {
+    async fn get_priority_fee(&self, target: ConfirmationTarget) -> Result<u64> {
+        let tiers = swap::fetch_priority_fee(self.http_client, self.priority_fee_url).await?;
+        Ok(tier_for(target, &tiers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_env_str_is_case_insensitive() {
+        assert_eq!(
+            ConfirmationTarget::from_env_str("Fast"),
+            Some(ConfirmationTarget::Fast)
+        );
+        assert_eq!(
+            ConfirmationTarget::from_env_str("ECONOMY"),
+            Some(ConfirmationTarget::Economy)
+        );
+        assert_eq!(ConfirmationTarget::from_env_str("bogus"), None);
+    }
+
+    #[test]
+    fn escalate_steps_economy_to_normal_to_fast_then_stops() {
+        assert_eq!(
+            ConfirmationTarget::Economy.escalate(),
+            Some(ConfirmationTarget::Normal)
+        );
+        assert_eq!(
+            ConfirmationTarget::Normal.escalate(),
+            Some(ConfirmationTarget::Fast)
+        );
+        assert_eq!(ConfirmationTarget::Fast.escalate(), None);
+    }
+
+    #[test]
+    fn tier_for_selects_the_matching_fee_tier() {
+        let tiers = FeeTiers {
+            vh: 300,
+            h: 200,
+            m: 100,
+        };
+        assert_eq!(tier_for(ConfirmationTarget::Fast, &tiers), 300);
+        assert_eq!(tier_for(ConfirmationTarget::Normal, &tiers), 200);
+        assert_eq!(tier_for(ConfirmationTarget::Economy, &tiers), 100);
+    }
+}
@@ -0,0 +1,285 @@
+//! Crash-resilience for multi-leg swaps. Each swap gets a JSON file keyed
+//! by a generated swap id, recording the swap's parameters and each leg's
+//! signature/confirmation status as the send loop progresses. A
+//! `resume <swap_id>` run reloads the journal, reconciles it against the
+//! chain, and continues from the first unconfirmed leg instead of
+//! resending legs that already landed.
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::transaction::VersionedTransaction;
+use std::{fs, path::PathBuf};
+use uuid::Uuid;
+
+use crate::swap::{SwapMode, SwapParams};
+
+const JOURNAL_DIR: &str = ".swap-journal";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LegState {
+    /// Base64(bincode) of the `VersionedTransaction` as built for this leg,
+    /// so a resumed swap replays the exact route it started with instead of
+    /// re-deriving one from a fresh quote that may no longer match the
+    /// legs already sent.
+    pub transaction: String,
+    pub signature: Option<String>,
+    pub confirmed: bool,
+    /// Set by [`SwapJournal::reconcile`] when the sent transaction landed
+    /// but failed on-chain (e.g. slippage exceeded), so `resume` can report
+    /// the failure instead of resending a transaction that can never
+    /// confirm.
+    #[serde(default)]
+    pub failed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SwapJournal {
+    pub swap_id: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub tx_version: String,
+    pub mode: SwapMode,
+    pub legs: Vec<LegState>,
+}
+
+impl SwapJournal {
+    /// Builds a journal for a freshly-built leg set, persisting each leg's
+    /// decoded transaction so it can be replayed verbatim on resume.
+    pub fn new(params: &SwapParams, legs: &[VersionedTransaction]) -> Result<Self> {
+        let legs = legs
+            .iter()
+            .map(|vtx| -> Result<LegState> {
+                let bytes =
+                    bincode::serialize(vtx).context("Failed to serialize leg transaction")?;
+                Ok(LegState {
+                    transaction: base64::engine::general_purpose::STANDARD.encode(bytes),
+                    signature: None,
+                    confirmed: false,
+                    failed: false,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SwapJournal {
+            swap_id: Uuid::new_v4().to_string(),
+            input_mint: params.input_mint.clone(),
+            output_mint: params.output_mint.clone(),
+            amount: params.amount,
+            slippage_bps: params.slippage_bps,
+            tx_version: params.tx_version.clone(),
+            mode: params.mode,
+            legs,
+        })
+    }
+
+    /// Decodes every leg's persisted transaction back into a
+    /// `VersionedTransaction`, in leg order.
+    pub fn transactions(&self) -> Result<Vec<VersionedTransaction>> {
+        self.legs
+            .iter()
+            .enumerate()
+            .map(|(i, leg)| {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(&leg.transaction)
+                    .with_context(|| format!("Leg {}: failed to Base64-decode transaction", i + 1))?;
+                bincode::deserialize(&bytes).with_context(|| {
+                    format!("Leg {}: failed to bincode-deserialize VersionedTransaction", i + 1)
+                })
+            })
+            .collect()
+    }
+
+    fn path_for(swap_id: &str) -> PathBuf {
+        PathBuf::from(JOURNAL_DIR).join(format!("{}.json", swap_id))
+    }
+
+    pub fn path(&self) -> PathBuf {
+        Self::path_for(&self.swap_id)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(JOURNAL_DIR).context("Failed to create swap journal directory")?;
+        let path = self.path();
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize swap journal")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write swap journal {:?}", path))
+    }
+
+    pub fn load(swap_id: &str) -> Result<Self> {
+        let path = Self::path_for(swap_id);
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read swap journal {:?}", path))?;
+        serde_json::from_str(&json).context("Failed to parse swap journal")
+    }
+
+    /// Records that `vtx` was just (re)sent for `leg_index`, overwriting the
+    /// leg's persisted transaction bytes along with its signature. A fee
+    /// escalation rebuilds every leg with a bumped compute-unit price and a
+    /// fresh blockhash, so the journal must track whichever transaction was
+    /// actually broadcast, not just the one built on the first attempt —
+    /// otherwise `resume` would replay a stale transaction that no longer
+    /// matches the signature on record.
+    pub fn record_sent(
+        &mut self,
+        leg_index: usize,
+        vtx: &VersionedTransaction,
+        signature: &str,
+    ) -> Result<()> {
+        let leg_count = self.legs.len();
+        let swap_id = self.swap_id.clone();
+        let bytes = bincode::serialize(vtx).context("Failed to serialize leg transaction")?;
+        let leg = self.legs.get_mut(leg_index).ok_or_else(|| {
+            anyhow!(
+                "leg {} out of range for swap {} ({} legs recorded)",
+                leg_index,
+                swap_id,
+                leg_count
+            )
+        })?;
+        leg.transaction = base64::engine::general_purpose::STANDARD.encode(bytes);
+        leg.signature = Some(signature.to_string());
+        self.save()
+    }
+
+    pub fn record_confirmed(&mut self, leg_index: usize) -> Result<()> {
+        let leg_count = self.legs.len();
+        let swap_id = self.swap_id.clone();
+        let leg = self.legs.get_mut(leg_index).ok_or_else(|| {
+            anyhow!(
+                "leg {} out of range for swap {} ({} legs recorded)",
+                leg_index,
+                swap_id,
+                leg_count
+            )
+        })?;
+        leg.confirmed = true;
+        self.save()
+    }
+
+    pub fn first_unconfirmed_leg(&self) -> usize {
+        self.legs
+            .iter()
+            .position(|leg| !leg.confirmed)
+            .unwrap_or(self.legs.len())
+    }
+
+    /// The first leg [`Self::reconcile`] found landed but failed on-chain,
+    /// if any. A failed leg can never confirm, so it must be surfaced
+    /// rather than silently retried.
+    pub fn first_failed_leg(&self) -> Option<usize> {
+        self.legs.iter().position(|leg| leg.failed)
+    }
+
+    /// Re-checks every sent-but-not-yet-confirmed leg against the chain, in
+    /// case it landed after the crash but before the journal recorded it.
+    /// A leg whose transaction landed but failed on-chain (e.g. slippage
+    /// exceeded) is marked `failed` rather than left looking identical to a
+    /// leg that simply hasn't been checked yet.
+    pub fn reconcile(&mut self, rpc_client: &RpcClient) -> Result<()> {
+        for leg in self.legs.iter_mut() {
+            if leg.confirmed {
+                continue;
+            }
+            let Some(sig) = &leg.signature else {
+                continue;
+            };
+            let signature: solana_sdk::signature::Signature =
+                sig.parse().context("Invalid signature in swap journal")?;
+            if let Some(status) = rpc_client
+                .get_signature_status(&signature)
+                .context("Failed to query signature status")?
+            {
+                match status {
+                    Ok(()) => leg.confirmed = true,
+                    Err(_) => leg.failed = true,
+                }
+            }
+        }
+        self.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_with_legs(confirmed: &[bool]) -> SwapJournal {
+        SwapJournal {
+            swap_id: "test-swap".to_string(),
+            input_mint: "So11111111111111111111111111111111111111112".to_string(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            amount: 1_000_000,
+            slippage_bps: 50,
+            tx_version: "V0".to_string(),
+            mode: SwapMode::BaseIn,
+            legs: confirmed
+                .iter()
+                .map(|&confirmed| LegState {
+                    transaction: String::new(),
+                    signature: None,
+                    confirmed,
+                    failed: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn first_unconfirmed_leg_finds_the_first_gap() {
+        let journal = journal_with_legs(&[true, true, false, false]);
+        assert_eq!(journal.first_unconfirmed_leg(), 2);
+    }
+
+    #[test]
+    fn first_unconfirmed_leg_is_past_the_end_when_all_confirmed() {
+        let journal = journal_with_legs(&[true, true]);
+        assert_eq!(journal.first_unconfirmed_leg(), 2);
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let journal = journal_with_legs(&[true, false]);
+        journal.save().expect("save should succeed");
+
+        let loaded = SwapJournal::load(&journal.swap_id).expect("load should succeed");
+        fs::remove_file(journal.path()).expect("cleanup should succeed");
+
+        assert_eq!(loaded.swap_id, journal.swap_id);
+        assert_eq!(loaded.input_mint, journal.input_mint);
+        assert_eq!(loaded.legs.len(), journal.legs.len());
+        assert_eq!(loaded.first_unconfirmed_leg(), 1);
+    }
+
+    #[test]
+    fn record_sent_rejects_an_out_of_range_leg() {
+        let mut journal = journal_with_legs(&[false]);
+        let result = journal.record_sent(5, &VersionedTransaction::default(), "deadbeef");
+        assert!(result.is_err());
+        fs::remove_file(journal.path()).ok();
+    }
+
+    #[test]
+    fn record_sent_overwrites_the_persisted_transaction() {
+        let mut journal = journal_with_legs(&[false]);
+        journal
+            .record_sent(0, &VersionedTransaction::default(), "deadbeef")
+            .expect("record_sent should succeed");
+        fs::remove_file(journal.path()).ok();
+
+        assert_eq!(journal.legs[0].signature.as_deref(), Some("deadbeef"));
+        assert!(!journal.legs[0].transaction.is_empty());
+    }
+
+    #[test]
+    fn first_failed_leg_finds_the_marked_leg() {
+        let mut journal = journal_with_legs(&[true, false, false]);
+        assert_eq!(journal.first_failed_leg(), None);
+
+        journal.legs[1].failed = true;
+        assert_eq!(journal.first_failed_leg(), Some(1));
+    }
+}
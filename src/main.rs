@@ -1,74 +1,54 @@
 use anyhow::{anyhow, Context, Result};
-use base64::Engine as _;
-use bincode;
 use dotenv::dotenv;
 use reqwest::Client;
-use serde::Deserialize;
-use serde_json::json;
-use solana_client::{
-    rpc_client::RpcClient,
-    rpc_config::RpcSendTransactionConfig,
-};
+use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     signature::{Keypair, Signer},
-    transaction::VersionedTransaction,
-};
-use std::{
-    env,
-    fs::File,
-    io::Read,
-    path::PathBuf,
 };
+use std::{env, fs::File, io::Read, path::PathBuf};
 
- 
-const PRIORITY_FEE_URL: &str = "https://api-v3.raydium.io/main/auto-fee";
-const SWAP_BASE:        &str = "https://transaction-v1.raydium.io";
-
- 
-const RPC_URL: &str = "https://api.mainnet-beta.solana.com";
-
-const WRAP_SOL:   bool = true;
-const UNWRAP_SOL: bool = false;
-
-#[derive(Deserialize)]
-struct PriorityFeeResponse {
-    data: PriorityFeeDataWrapper,
-}
-
-#[derive(Deserialize)]
-struct PriorityFeeDataWrapper {
-    default: FeeTiers,
-}
-
-#[derive(Deserialize)]
-struct FeeTiers {
-    vh: u64,
-    h:  u64,
-    m:  u64,
-}
+mod cluster;
+mod fee;
+mod journal;
+mod rpc_service;
+mod swap;
 
-#[derive(Deserialize)]
-struct SwapTransactionResponse {
-    data: Vec<SwapTxObject>,
-}
+use cluster::Endpoints;
+use fee::{AutoFeeEstimator, ConfirmationTarget};
+use journal::SwapJournal;
+use swap::{SwapMode, SwapParams};
 
-#[derive(Deserialize)]
-struct SwapTxObject {
-    transaction: String,
-}
+const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:8899";
+const DEFAULT_AIRDROP_LAMPORTS: u64 = 1_000_000_000;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    
     dotenv().ok();
 
-    let keypair_path = env::var("KEYPAIR_PATH")
-        .context("KEYPAIR_PATH must be set in .env")?;
-    let input_mint = env::var("INPUT_MINT")
-        .context("INPUT_MINT must be set in .env")?;
-    let output_mint = env::var("OUTPUT_MINT")
-        .context("OUTPUT_MINT must be set in .env")?;
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("serve") => {
+            let keypair_path =
+                env::var("KEYPAIR_PATH").context("KEYPAIR_PATH must be set in .env")?;
+            let listen_addr =
+                env::var("LISTEN_ADDR").unwrap_or_else(|_| DEFAULT_LISTEN_ADDR.to_string());
+            let endpoints = Endpoints::from_env();
+            rpc_service::run(endpoints, &keypair_path, &listen_addr).await
+        }
+        Some("resume") => {
+            let swap_id = args.next().context("usage: resume <swap_id>")?;
+            run_resume_swap(&swap_id).await
+        }
+        Some("airdrop") => run_airdrop().await,
+        _ => run_one_shot_swap().await,
+    }
+}
+
+async fn run_one_shot_swap() -> Result<()> {
+    let keypair_path = env::var("KEYPAIR_PATH").context("KEYPAIR_PATH must be set in .env")?;
+    let input_mint = env::var("INPUT_MINT").context("INPUT_MINT must be set in .env")?;
+    let output_mint = env::var("OUTPUT_MINT").context("OUTPUT_MINT must be set in .env")?;
     let amount: u64 = env::var("AMOUNT")
         .context("AMOUNT must be set in .env")?
         .parse()
@@ -77,153 +57,138 @@ async fn main() -> Result<()> {
         .context("SLIPPAGE_BPS must be set in .env")?
         .parse()
         .context("SLIPPAGE_BPS must be a valid u64")?;
-    let tx_version = env::var("TX_VERSION")
-        .context("TX_VERSION must be set in .env")?;
- 
+    let tx_version = env::var("TX_VERSION").context("TX_VERSION must be set in .env")?;
+
     let owner = read_keypair_from_file(&keypair_path)
         .with_context(|| format!("Failed to read keypair from {}", keypair_path))?;
 
-    let rpc_client = RpcClient::new_with_commitment(
-        RPC_URL.to_string(),
-        CommitmentConfig::confirmed(),
-    );
+    let endpoints = Endpoints::from_env();
+    let rpc_client =
+        RpcClient::new_with_commitment(endpoints.rpc_url.clone(), CommitmentConfig::confirmed());
 
-   
     let http_client = Client::new();
 
-    println!("Calling priority-fee at: {}", PRIORITY_FEE_URL);
-    let fee_resp = http_client
-        .get(PRIORITY_FEE_URL)
-        .send()
-        .await
-        .context("Failed to call priority-fee endpoint")?;
-    if !fee_resp.status().is_success() {
-        return Err(anyhow!("priority-fee endpoint returned HTTP {}", fee_resp.status()));
-    }
-    let fee_json: PriorityFeeResponse = fee_resp
-        .json()
-        .await
-        .context("Failed to parse priority-fee JSON")?;
-    let high_fee: u64 = fee_json.data.default.h;
-    println!("Using 'high' fee tier = {} micro-lamports", high_fee);
-
-    let quote_url = format!(
-        "{}/compute/swap-base-in?inputMint={}&outputMint={}&amount={}&slippageBps={}&txVersion={}",
-        SWAP_BASE, input_mint, output_mint, amount, slippage_bps, tx_version
-    );
-    println!("Fetching swap quote from: {}", quote_url);
-
-    let quote_resp = http_client
-        .get(&quote_url)
-        .send()
-        .await
-        .context("Failed to call compute/swap-base-in")?;
-    if !quote_resp.status().is_success() {
-        return Err(anyhow!("compute/swap-base-in returned HTTP {}", quote_resp.status()));
-    }
+    let target = env::var("CONFIRMATION_TARGET")
+        .ok()
+        .and_then(|s| ConfirmationTarget::from_env_str(&s))
+        .unwrap_or_default();
+    let mode = env::var("SWAP_MODE")
+        .ok()
+        .and_then(|s| SwapMode::from_env_str(&s))
+        .unwrap_or_default();
+    println!("swap mode: {:?}", mode);
+
+    let params = SwapParams {
+        input_mint,
+        output_mint,
+        amount,
+        slippage_bps,
+        tx_version,
+        mode,
+    };
+    let quote = swap::fetch_quote(&http_client, &endpoints.swap_base, &params).await?;
+
+    let fee_estimator = AutoFeeEstimator {
+        http_client: &http_client,
+        priority_fee_url: &endpoints.priority_fee_url,
+    };
+    swap::send_and_confirm_legs_with_escalation(
+        &http_client,
+        &endpoints.swap_base,
+        &rpc_client,
+        &owner,
+        &params,
+        &quote,
+        &fee_estimator,
+        target,
+    )
+    .await?;
 
-    let swap_response_json: serde_json::Value = quote_resp
-        .json()
-        .await
-        .context("Failed to parse swap quote JSON")?;
-
-   
-    if let Some(route) = swap_response_json.get("route") {
-        println!("–––––––––––––––––––––––––––––––");
-        println!("Detailed `marketKeys` for each leg in `route`:");
-        if let Some(array) = route.as_array() {
-            for (i, step) in array.iter().enumerate() {
-                if let Some(market_keys) = step.get("marketKeys") {
- 
-                    let pretty = serde_json::to_string_pretty(market_keys)
-                        .unwrap_or_else(|_| "\"<invalid JSON>\"".to_string());
-                    println!(" Leg {} marketKeys:\n{}\n", i + 1, pretty);
-                }
-            }
-        }
-        println!("–––––––––––––––––––––––––––––––");
-    }
-    // ──────────────────────────────────────────────────────────────────────────
-
- 
-    let tx_request_body = json!({
-        "computeUnitPriceMicroLamports": high_fee.to_string(),
-        "swapResponse": swap_response_json,
-        "txVersion": tx_version,
-        "wallet": owner.pubkey().to_string(),
-        "wrapSol": WRAP_SOL,
-        "unwrapSol": UNWRAP_SOL
-    });
-    let tx_url = format!("{}/transaction/swap-base-in", SWAP_BASE);
-    println!("Building swap transaction via: {}", tx_url);
-    let resp = http_client
-        .post(&tx_url)
-        .json(&tx_request_body)
-        .send()
-        .await
-        .context("Failed to call transaction/swap-base-in")?;
-    if !resp.status().is_success() {
-        return Err(anyhow!("transaction/swap-base-in returned HTTP {}", resp.status()));
+    Ok(())
+}
+
+/// Reloads a swap journal, reconciles it against the chain, and replays the
+/// persisted transactions for the first leg that hasn't confirmed yet.
+async fn run_resume_swap(swap_id: &str) -> Result<()> {
+    let keypair_path = env::var("KEYPAIR_PATH").context("KEYPAIR_PATH must be set in .env")?;
+    let owner = read_keypair_from_file(&keypair_path)
+        .with_context(|| format!("Failed to read keypair from {}", keypair_path))?;
+
+    let endpoints = Endpoints::from_env();
+    let rpc_client =
+        RpcClient::new_with_commitment(endpoints.rpc_url.clone(), CommitmentConfig::confirmed());
+
+    let mut journal = SwapJournal::load(swap_id)
+        .with_context(|| format!("Failed to load swap journal for {}", swap_id))?;
+    journal
+        .reconcile(&rpc_client)
+        .context("Failed to reconcile swap journal against chain state")?;
+
+    if journal.first_unconfirmed_leg() >= journal.legs.len() {
+        println!("swap {} already fully confirmed, nothing to resume", swap_id);
+        return Ok(());
     }
 
- 
-    let raw_json = resp
-        .text()
-        .await
-        .context("Failed to read response text from transaction/swap-base-in")?;
-
- 
-    println!("Raw /transaction/swap-base-in response JSON:\n{}", raw_json);
-
-   
-    let swap_tx_json: SwapTransactionResponse = serde_json::from_str(&raw_json)
-        .context("Failed to deserialize SwapTransactionResponse from raw JSON")?;
-
-  
-    let mut versioned_transactions = Vec::new();
-    for (i, obj) in swap_tx_json.data.iter().enumerate() {
-        let raw_bytes = base64::engine::general_purpose::STANDARD
-            .decode(&obj.transaction)
-            .with_context(|| format!("Leg {}: failed to Base64-decode transaction", i + 1))?;
-
-        let vtx: VersionedTransaction = bincode::deserialize(&raw_bytes)
-            .with_context(|| format!("Leg {}: failed to bincode-deserialize VersionedTransaction", i + 1))?;
-        versioned_transactions.push(vtx);
+    if let Some(i) = journal.first_failed_leg() {
+        return Err(anyhow!(
+            "swap {} leg {} landed but failed on-chain and cannot be resumed automatically; inspect the journal at {:?}",
+            swap_id,
+            i + 1,
+            journal.path()
+        ));
     }
-    println!("total {} transactions", versioned_transactions.len());
-
- 
-    for (i, vtx) in versioned_transactions.into_iter().enumerate() {
- 
-        let signed_vtx = VersionedTransaction::try_new(vtx.message.clone(), &[&owner])
-            .context("Failed to rebuild VersionedTransaction with signature")?;
-
-        println!("{} transaction sending...", i + 1);
-        let signature = rpc_client
-            .send_transaction_with_config(
-                &signed_vtx,
-                RpcSendTransactionConfig {
-                    skip_preflight: true,
-                    ..RpcSendTransactionConfig::default()
-                },
-            )
-            .context("Failed to send VersionedTransaction")?;
-
-        rpc_client
-            .confirm_transaction_with_commitment(&signature, CommitmentConfig::finalized())
-            .context("Failed to confirm transaction")?;
-
-        println!("{} transaction confirmed, txId: {}", i + 1, signature);
-        println!("🔍 http://solscan.io/tx/{}", signature);
+
+    swap::resume_and_confirm_legs(&rpc_client, &owner, &mut journal).await?;
+
+    Ok(())
+}
+
+/// Rejects `CLUSTER=mainnet` outright — `request_airdrop` would just fail
+/// there, but failing fast avoids a confusing RPC error.
+async fn run_airdrop() -> Result<()> {
+    let keypair_path = env::var("KEYPAIR_PATH").context("KEYPAIR_PATH must be set in .env")?;
+    let owner = read_keypair_from_file(&keypair_path)
+        .with_context(|| format!("Failed to read keypair from {}", keypair_path))?;
+
+    let endpoints = Endpoints::from_env();
+    if endpoints.cluster.is_mainnet() {
+        return Err(anyhow!(
+            "airdrop is not available on mainnet; set CLUSTER=devnet or CLUSTER=testnet"
+        ));
     }
 
+    let lamports: u64 = env::var("AIRDROP_LAMPORTS")
+        .ok()
+        .map(|s| s.parse().context("AIRDROP_LAMPORTS must be a valid u64"))
+        .transpose()?
+        .unwrap_or(DEFAULT_AIRDROP_LAMPORTS);
+
+    let rpc_client =
+        RpcClient::new_with_commitment(endpoints.rpc_url.clone(), CommitmentConfig::confirmed());
+
+    println!(
+        "requesting airdrop of {} lamports to {} on {:?}",
+        lamports,
+        owner.pubkey(),
+        endpoints.cluster
+    );
+    let signature = rpc_client
+        .request_airdrop(&owner.pubkey(), lamports)
+        .context("Failed to request airdrop")?;
+
+    rpc_client
+        .confirm_transaction_with_commitment(&signature, CommitmentConfig::finalized())
+        .context("Failed to confirm airdrop transaction")?;
+
+    println!("airdrop confirmed, txId: {}", signature);
+
     Ok(())
 }
 
 fn read_keypair_from_file(path: &str) -> Result<Keypair> {
     let path_buf = PathBuf::from(path);
-    let mut file = File::open(&path_buf)
-        .with_context(|| format!("Failed to open keypair file: {:?}", path_buf))?;
+    let mut file =
+        File::open(&path_buf).with_context(|| format!("Failed to open keypair file: {:?}", path_buf))?;
     let mut buf = String::new();
     file.read_to_string(&mut buf)
         .context("Failed to read keypair file as string")?;
@@ -0,0 +1,238 @@
+//! JSON-RPC/HTTP service exposing `getQuote`, `buildSwap`, and
+//! `executeSwap` over a shared `AppState` built once in [`run`].
+
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::{Keypair, Signer};
+use std::sync::Arc;
+
+use crate::cluster::Endpoints;
+use crate::fee::{AutoFeeEstimator, ConfirmationTarget, FeeEstimator};
+use crate::swap::{self, SwapMode, SwapParams};
+
+pub struct AppState {
+    pub rpc_client: RpcClient,
+    pub http_client: Client,
+    pub owner: Keypair,
+    pub swap_base: String,
+    pub priority_fee_url: String,
+}
+
+#[derive(Deserialize)]
+pub struct GetQuoteRequest {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub tx_version: String,
+    #[serde(default)]
+    pub mode: SwapMode,
+}
+
+#[derive(Serialize)]
+pub struct GetQuoteResponse {
+    pub quote: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+pub struct BuildSwapRequest {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub tx_version: String,
+    #[serde(default)]
+    pub mode: SwapMode,
+}
+
+#[derive(Serialize)]
+pub struct BuildSwapResponse {
+    pub leg_count: usize,
+}
+
+#[derive(Deserialize)]
+pub struct ExecuteSwapRequest {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub tx_version: String,
+    #[serde(default)]
+    pub mode: SwapMode,
+}
+
+#[derive(Serialize)]
+pub struct ExecuteSwapResponse {
+    pub leg_count: usize,
+    pub signatures: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct RpcError {
+    pub error: String,
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        RpcError {
+            error: format!("{:#}", err),
+        }
+    }
+}
+
+/// Every handler failure is a client-facing error (a bad request, an
+/// upstream Raydium/RPC failure, a broadcast that never confirmed), so a
+/// `RpcError` always maps to `500`. Axum defaults to `200` for any `Json<T>`
+/// regardless of where it sits in a `Result`, so without this impl a failed
+/// swap would read as a success to callers that check only the status code.
+impl IntoResponse for RpcError {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(self)).into_response()
+    }
+}
+
+async fn get_quote(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<GetQuoteRequest>,
+) -> Result<Json<GetQuoteResponse>, RpcError> {
+    let params = SwapParams {
+        input_mint: req.input_mint,
+        output_mint: req.output_mint,
+        amount: req.amount,
+        slippage_bps: req.slippage_bps,
+        tx_version: req.tx_version,
+        mode: req.mode,
+    };
+    let quote = swap::fetch_quote(&state.http_client, &state.swap_base, &params).await?;
+    Ok(Json(GetQuoteResponse { quote: quote.raw }))
+}
+
+async fn build_swap(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BuildSwapRequest>,
+) -> Result<Json<BuildSwapResponse>, RpcError> {
+    let params = SwapParams {
+        input_mint: req.input_mint,
+        output_mint: req.output_mint,
+        amount: req.amount,
+        slippage_bps: req.slippage_bps,
+        tx_version: req.tx_version,
+        mode: req.mode,
+    };
+    let quote = swap::fetch_quote(&state.http_client, &state.swap_base, &params).await?;
+    let fee_estimator = AutoFeeEstimator {
+        http_client: &state.http_client,
+        priority_fee_url: &state.priority_fee_url,
+    };
+    let fee = fee_estimator
+        .get_priority_fee(ConfirmationTarget::default())
+        .await?;
+    let txs = swap::build_swap_transactions(
+        &state.http_client,
+        &state.swap_base,
+        &state.owner,
+        &quote,
+        &params.tx_version,
+        params.mode,
+        fee,
+    )
+    .await?;
+    Ok(Json(BuildSwapResponse {
+        leg_count: txs.len(),
+    }))
+}
+
+/// `fetch_quote` is a plain `reqwest` call and cooperates fine with the
+/// async runtime, so only the part that drives `RpcClient`'s blocking
+/// send/confirm calls runs on the blocking thread pool — otherwise a slow
+/// Raydium response would tie up a blocking-pool thread before there's any
+/// blocking work to do.
+async fn execute_swap(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ExecuteSwapRequest>,
+) -> Result<Json<ExecuteSwapResponse>, RpcError> {
+    let params = SwapParams {
+        input_mint: req.input_mint,
+        output_mint: req.output_mint,
+        amount: req.amount,
+        slippage_bps: req.slippage_bps,
+        tx_version: req.tx_version,
+        mode: req.mode,
+    };
+
+    let quote = swap::fetch_quote(&state.http_client, &state.swap_base, &params).await?;
+
+    let (signatures, journal) = tokio::task::spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let fee_estimator = AutoFeeEstimator {
+                http_client: &state.http_client,
+                priority_fee_url: &state.priority_fee_url,
+            };
+            swap::send_and_confirm_legs_with_escalation(
+                &state.http_client,
+                &state.swap_base,
+                &state.rpc_client,
+                &state.owner,
+                &params,
+                &quote,
+                &fee_estimator,
+                ConfirmationTarget::default(),
+            )
+            .await
+        })
+    })
+    .await
+    .map_err(|e| anyhow!("swap task panicked: {e}"))??;
+
+    println!("swap id: {}", journal.swap_id);
+    Ok(Json(ExecuteSwapResponse {
+        leg_count: signatures.len(),
+        signatures: signatures.iter().map(|s| s.to_string()).collect(),
+    }))
+}
+
+/// Starts the JSON-RPC/HTTP service and blocks until it is shut down.
+pub async fn run(endpoints: Endpoints, keypair_path: &str, listen_addr: &str) -> Result<()> {
+    let owner = crate::read_keypair_from_file(keypair_path)
+        .with_context(|| format!("Failed to read keypair from {}", keypair_path))?;
+    println!("swap daemon: wallet = {}", owner.pubkey());
+
+    let rpc_client = RpcClient::new_with_commitment(
+        endpoints.rpc_url.clone(),
+        solana_sdk::commitment_config::CommitmentConfig::confirmed(),
+    );
+    let http_client = Client::new();
+
+    let state = Arc::new(AppState {
+        rpc_client,
+        http_client,
+        owner,
+        swap_base: endpoints.swap_base,
+        priority_fee_url: endpoints.priority_fee_url,
+    });
+
+    let app = Router::new()
+        .route("/getQuote", post(get_quote))
+        .route("/buildSwap", post(build_swap))
+        .route("/executeSwap", post(execute_swap))
+        .with_state(state);
+
+    println!("swap daemon listening on {}", listen_addr);
+    let listener = tokio::net::TcpListener::bind(listen_addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", listen_addr))?;
+    axum::serve(listener, app)
+        .await
+        .context("JSON-RPC/HTTP service exited")?;
+
+    Ok(())
+}
@@ -0,0 +1,429 @@
+use anyhow::{anyhow, Context, Result};
+use base64::Engine as _;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
+use solana_sdk::{
+    signature::{Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+use std::time::{Duration, Instant};
+
+use crate::fee::{ConfirmationTarget, FeeEstimator};
+use crate::journal::SwapJournal;
+
+pub const PRIORITY_FEE_URL: &str = "https://api-v3.raydium.io/main/auto-fee";
+pub const SWAP_BASE: &str = "https://transaction-v1.raydium.io";
+
+pub const WRAP_SOL: bool = true;
+pub const UNWRAP_SOL: bool = false;
+
+/// How many times the send loop will bump the fee tier and resubmit before
+/// giving up on a stalled broadcast.
+pub const MAX_FEE_ESCALATIONS: u32 = 3;
+/// How long to wait for a leg to confirm before treating it as stalled.
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+pub struct PriorityFeeResponse {
+    pub data: PriorityFeeDataWrapper,
+}
+
+#[derive(Deserialize)]
+pub struct PriorityFeeDataWrapper {
+    pub default: FeeTiers,
+}
+
+#[derive(Deserialize)]
+pub struct FeeTiers {
+    pub vh: u64,
+    pub h: u64,
+    pub m: u64,
+}
+
+#[derive(Deserialize)]
+pub struct SwapTransactionResponse {
+    pub data: Vec<SwapTxObject>,
+}
+
+#[derive(Deserialize)]
+pub struct SwapTxObject {
+    pub transaction: String,
+}
+
+/// Whether `amount` names the input to spend or the output to receive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum SwapMode {
+    /// `amount` is the exact input; slippage bounds the minimum output.
+    #[default]
+    BaseIn,
+    /// `amount` is the exact output; slippage bounds the maximum input.
+    BaseOut,
+}
+
+impl SwapMode {
+    pub fn from_env_str(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "base-in" => Some(SwapMode::BaseIn),
+            "base-out" => Some(SwapMode::BaseOut),
+            _ => None,
+        }
+    }
+
+    fn path_segment(self) -> &'static str {
+        match self {
+            SwapMode::BaseIn => "swap-base-in",
+            SwapMode::BaseOut => "swap-base-out",
+        }
+    }
+}
+
+/// Parameters for a single swap, whatever drives them (env vars today, an
+/// RPC request tomorrow).
+pub struct SwapParams {
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount: u64,
+    pub slippage_bps: u64,
+    pub tx_version: String,
+    pub mode: SwapMode,
+}
+
+/// Outcome of fetching a quote, kept separate from the raw JSON so callers
+/// (CLI printing, RPC responses) can each present it how they like.
+pub struct Quote {
+    pub raw: serde_json::Value,
+}
+
+pub async fn fetch_priority_fee(http_client: &Client, priority_fee_url: &str) -> Result<FeeTiers> {
+    println!("Calling priority-fee at: {}", priority_fee_url);
+    let fee_resp = http_client
+        .get(priority_fee_url)
+        .send()
+        .await
+        .context("Failed to call priority-fee endpoint")?;
+    if !fee_resp.status().is_success() {
+        return Err(anyhow!(
+            "priority-fee endpoint returned HTTP {}",
+            fee_resp.status()
+        ));
+    }
+    let fee_json: PriorityFeeResponse = fee_resp
+        .json()
+        .await
+        .context("Failed to parse priority-fee JSON")?;
+    Ok(fee_json.data.default)
+}
+
+pub async fn fetch_quote(http_client: &Client, swap_base: &str, params: &SwapParams) -> Result<Quote> {
+    let path = params.mode.path_segment();
+    let quote_url = format!(
+        "{}/compute/{}?inputMint={}&outputMint={}&amount={}&slippageBps={}&txVersion={}",
+        swap_base, path, params.input_mint, params.output_mint, params.amount, params.slippage_bps, params.tx_version
+    );
+    println!("Fetching swap quote from: {}", quote_url);
+
+    let quote_resp = http_client
+        .get(&quote_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to call compute/{}", path))?;
+    if !quote_resp.status().is_success() {
+        return Err(anyhow!(
+            "compute/{} returned HTTP {}",
+            path,
+            quote_resp.status()
+        ));
+    }
+
+    let raw: serde_json::Value = quote_resp
+        .json()
+        .await
+        .context("Failed to parse swap quote JSON")?;
+
+    if let Some(route) = raw.get("route") {
+        println!("–––––––––––––––––––––––––––––––");
+        println!("Detailed `marketKeys` for each leg in `route`:");
+        if let Some(array) = route.as_array() {
+            for (i, step) in array.iter().enumerate() {
+                if let Some(market_keys) = step.get("marketKeys") {
+                    let pretty = serde_json::to_string_pretty(market_keys)
+                        .unwrap_or_else(|_| "\"<invalid JSON>\"".to_string());
+                    println!(" Leg {} marketKeys:\n{}\n", i + 1, pretty);
+                }
+            }
+        }
+        println!("–––––––––––––––––––––––––––––––");
+    }
+
+    if params.mode == SwapMode::BaseOut {
+        if let Some(max_input) = raw
+            .get("data")
+            .and_then(|d| d.get("otherAmountThreshold"))
+        {
+            println!(
+                "swap-base-out: requesting exact output {}, worst-case input = {}",
+                params.amount, max_input
+            );
+        }
+    }
+
+    Ok(Quote { raw })
+}
+
+pub async fn build_swap_transactions(
+    http_client: &Client,
+    swap_base: &str,
+    owner: &Keypair,
+    quote: &Quote,
+    tx_version: &str,
+    mode: SwapMode,
+    compute_unit_price_micro_lamports: u64,
+) -> Result<Vec<VersionedTransaction>> {
+    let path = mode.path_segment();
+    let tx_request_body = json!({
+        "computeUnitPriceMicroLamports": compute_unit_price_micro_lamports.to_string(),
+        "swapResponse": quote.raw,
+        "txVersion": tx_version,
+        "wallet": owner.pubkey().to_string(),
+        "wrapSol": WRAP_SOL,
+        "unwrapSol": UNWRAP_SOL
+    });
+    let tx_url = format!("{}/transaction/{}", swap_base, path);
+    println!("Building swap transaction via: {}", tx_url);
+    let resp = http_client
+        .post(&tx_url)
+        .json(&tx_request_body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to call transaction/{}", path))?;
+    if !resp.status().is_success() {
+        return Err(anyhow!(
+            "transaction/{} returned HTTP {}",
+            path,
+            resp.status()
+        ));
+    }
+
+    let raw_json = resp
+        .text()
+        .await
+        .with_context(|| format!("Failed to read response text from transaction/{}", path))?;
+
+    println!("Raw /transaction/{} response JSON:\n{}", path, raw_json);
+
+    let swap_tx_json: SwapTransactionResponse = serde_json::from_str(&raw_json)
+        .context("Failed to deserialize SwapTransactionResponse from raw JSON")?;
+
+    let mut versioned_transactions = Vec::new();
+    for (i, obj) in swap_tx_json.data.iter().enumerate() {
+        let raw_bytes = base64::engine::general_purpose::STANDARD
+            .decode(&obj.transaction)
+            .with_context(|| format!("Leg {}: failed to Base64-decode transaction", i + 1))?;
+
+        let vtx: VersionedTransaction = bincode::deserialize(&raw_bytes)
+            .with_context(|| format!("Leg {}: failed to bincode-deserialize VersionedTransaction", i + 1))?;
+        versioned_transactions.push(vtx);
+    }
+    println!("total {} transactions", versioned_transactions.len());
+
+    Ok(versioned_transactions)
+}
+
+/// Polls `get_signature_status` instead of blocking on
+/// `confirm_transaction_with_commitment`, so a stalled broadcast can be
+/// detected and escalated rather than hanging indefinitely.
+fn poll_confirmation(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    timeout: Duration,
+) -> Result<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = rpc_client
+            .get_signature_status(signature)
+            .context("Failed to query signature status")?
+        {
+            status.context("Transaction landed but failed on-chain")?;
+            return Ok(true);
+        }
+        if Instant::now() >= deadline {
+            return Ok(false);
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Signs and sends every leg, escalating the priority-fee tier and
+/// resubmitting a leg that stalls past [`CONFIRM_TIMEOUT`], up to
+/// [`MAX_FEE_ESCALATIONS`] times.
+///
+/// The [`SwapJournal`] is created lazily, on the first build inside the
+/// loop, since that's the first point the leg count is known; from then on
+/// it persists every send/confirm state change so a crash mid-swap can be
+/// resumed with `resume <swap_id>` via [`resume_and_confirm_legs`].
+#[allow(clippy::too_many_arguments)]
+pub async fn send_and_confirm_legs_with_escalation(
+    http_client: &Client,
+    swap_base: &str,
+    rpc_client: &RpcClient,
+    owner: &Keypair,
+    params: &SwapParams,
+    quote: &Quote,
+    fee_estimator: &dyn FeeEstimator,
+    mut target: ConfirmationTarget,
+) -> Result<(Vec<Signature>, SwapJournal)> {
+    let tx_version = &params.tx_version;
+    let mut signatures = Vec::new();
+    let mut escalations = 0;
+    let mut journal: Option<SwapJournal> = None;
+
+    loop {
+        let fee = fee_estimator.get_priority_fee(target).await?;
+        println!(
+            "Using '{:?}' fee tier = {} micro-lamports (attempt {})",
+            target, fee, escalations + 1
+        );
+        let txs =
+            build_swap_transactions(
+                http_client,
+                swap_base,
+                owner,
+                quote,
+                tx_version,
+                params.mode,
+                fee,
+            )
+            .await?;
+
+        let journal = match &mut journal {
+            Some(j) => j,
+            None => {
+                let j = SwapJournal::new(params, &txs)?;
+                j.save().context("Failed to persist new swap journal")?;
+                println!("swap id: {} (journal: {:?})", j.swap_id, j.path());
+                journal.insert(j)
+            }
+        };
+        let start_leg = journal.first_unconfirmed_leg();
+
+        let mut stalled = false;
+        for (i, vtx) in txs.into_iter().enumerate().skip(start_leg) {
+            let signed_vtx = VersionedTransaction::try_new(vtx.message.clone(), &[owner])
+                .context("Failed to rebuild VersionedTransaction with signature")?;
+
+            println!("{} transaction sending...", i + 1);
+            let signature = rpc_client
+                .send_transaction_with_config(
+                    &signed_vtx,
+                    RpcSendTransactionConfig {
+                        skip_preflight: true,
+                        ..RpcSendTransactionConfig::default()
+                    },
+                )
+                .context("Failed to send VersionedTransaction")?;
+            journal.record_sent(i, &vtx, &signature.to_string())?;
+
+            let confirmed = poll_confirmation(rpc_client, &signature, CONFIRM_TIMEOUT)?;
+            if !confirmed {
+                if escalations >= MAX_FEE_ESCALATIONS {
+                    return Err(anyhow!(
+                        "leg {} did not confirm within {:?} after {} fee escalations (swap id: {})",
+                        i + 1,
+                        CONFIRM_TIMEOUT,
+                        escalations,
+                        journal.swap_id
+                    ));
+                }
+                println!(
+                    "leg {} did not confirm within {:?}, escalating fee tier and rebuilding",
+                    i + 1,
+                    CONFIRM_TIMEOUT
+                );
+                target = target.escalate().unwrap_or(target);
+                escalations += 1;
+                stalled = true;
+                let backoff = Duration::from_secs(2u64.pow(escalations));
+                println!("backing off {:?} before retrying", backoff);
+                tokio::time::sleep(backoff).await;
+                break;
+            }
+
+            println!("{} transaction confirmed, txId: {}", i + 1, signature);
+            println!("🔍 http://solscan.io/tx/{}", signature);
+            journal.record_confirmed(i)?;
+            signatures.push(signature);
+        }
+
+        if !stalled {
+            return Ok((signatures, journal.clone()));
+        }
+    }
+}
+
+/// Replays the transactions persisted in `journal` from the first
+/// unconfirmed leg, instead of re-deriving a route from a fresh quote:
+/// by the time a swap is resumed, earlier legs have already landed, and a
+/// fresh quote has no way to account for that.
+pub async fn resume_and_confirm_legs(
+    rpc_client: &RpcClient,
+    owner: &Keypair,
+    journal: &mut SwapJournal,
+) -> Result<Vec<Signature>> {
+    let txs = journal.transactions()?;
+    let start_leg = journal.first_unconfirmed_leg();
+    let mut signatures = Vec::new();
+
+    for (i, vtx) in txs.into_iter().enumerate().skip(start_leg) {
+        let signed_vtx = VersionedTransaction::try_new(vtx.message.clone(), &[owner])
+            .context("Failed to rebuild VersionedTransaction with signature")?;
+
+        println!("{} transaction sending (resumed)...", i + 1);
+        let signature = rpc_client
+            .send_transaction_with_config(
+                &signed_vtx,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..RpcSendTransactionConfig::default()
+                },
+            )
+            .context("Failed to send VersionedTransaction")?;
+        journal.record_sent(i, &vtx, &signature.to_string())?;
+
+        let confirmed = poll_confirmation(rpc_client, &signature, CONFIRM_TIMEOUT)?;
+        if !confirmed {
+            return Err(anyhow!(
+                "leg {} did not confirm within {:?} (swap id: {})",
+                i + 1,
+                CONFIRM_TIMEOUT,
+                journal.swap_id
+            ));
+        }
+
+        println!("{} transaction confirmed, txId: {}", i + 1, signature);
+        println!("🔍 http://solscan.io/tx/{}", signature);
+        journal.record_confirmed(i)?;
+        signatures.push(signature);
+    }
+
+    Ok(signatures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_mode_from_env_str_is_case_insensitive() {
+        assert_eq!(SwapMode::from_env_str("base-in"), Some(SwapMode::BaseIn));
+        assert_eq!(SwapMode::from_env_str("BASE-OUT"), Some(SwapMode::BaseOut));
+        assert_eq!(SwapMode::from_env_str("bogus"), None);
+    }
+
+    #[test]
+    fn swap_mode_path_segment_matches_the_raydium_route() {
+        assert_eq!(SwapMode::BaseIn.path_segment(), "swap-base-in");
+        assert_eq!(SwapMode::BaseOut.path_segment(), "swap-base-out");
+    }
+}